@@ -1,7 +1,67 @@
 use crate::arena::Arena;
-use std::{num::NonZeroU32, ops};
+use std::{fmt, num::NonZeroU32, ops};
 
-pub type Alignment = NonZeroU32;
+/// Alignment information for a type, guaranteed to be a power of two.
+///
+/// This differs from a plain `NonZeroU32` in that the bit tricks used to
+/// round offsets up to a multiple of the alignment (`n & (align - 1)`)
+/// are only valid when `align` is itself a power of two, so we enforce
+/// that at construction time instead of every call site.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Alignment(NonZeroU32);
+
+impl Alignment {
+    pub const ONE: Self = Self(unsafe { NonZeroU32::new_unchecked(1) });
+    pub const TWO: Self = Self(unsafe { NonZeroU32::new_unchecked(2) });
+    pub const FOUR: Self = Self(unsafe { NonZeroU32::new_unchecked(4) });
+    pub const EIGHT: Self = Self(unsafe { NonZeroU32::new_unchecked(8) });
+    pub const SIXTEEN: Self = Self(unsafe { NonZeroU32::new_unchecked(16) });
+
+    /// The minimum alignment a struct or array must have when it is used
+    /// in the `Uniform` address space.
+    pub const MIN_UNIFORM: Self = Self::SIXTEEN;
+
+    pub const fn new(n: u32) -> Option<Self> {
+        match NonZeroU32::new(n) {
+            Some(n) if n.is_power_of_two() => Some(Self(n)),
+            _ => None,
+        }
+    }
+
+    /// Construct the alignment matching a scalar/vector component width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or not a power of two. For widths coming
+    /// from untrusted IR, prefer a path that can report a `LayoutError`
+    /// instead of panicking.
+    pub const fn from_width(width: u8) -> Self {
+        match Self::new(width as u32) {
+            Some(alignment) => alignment,
+            None => panic!("width must be a nonzero power of two"),
+        }
+    }
+
+    pub const fn get(&self) -> u32 {
+        self.0.get()
+    }
+
+    /// Return `true` if `n` is a multiple of this alignment.
+    pub const fn is_aligned(&self, n: u32) -> bool {
+        n & (self.0.get() - 1) == 0
+    }
+
+    /// Round `n` up to the nearest multiple of this alignment.
+    pub const fn round_up(&self, n: u32) -> u32 {
+        (n + self.0.get() - 1) & !(self.0.get() - 1)
+    }
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.get().fmt(f)
+    }
+}
 
 /// Alignment information for a type.
 #[derive(Clone, Copy, Debug, Hash, PartialEq)]
@@ -10,36 +70,122 @@ pub struct TypeLayout {
     pub alignment: Alignment,
 }
 
+/// An error produced while computing the layout of a module's types.
+///
+/// These all indicate IR that is malformed in some way: either it came
+/// from a front-end that doesn't validate its input, or it uses a
+/// specialization/override constant that hasn't been resolved yet. None
+/// of these should occur for a module that has passed validation.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum LayoutError {
+    #[error("Array size {0:?} doesn't refer to a constant integer scalar")]
+    InvalidArraySize(crate::Handle<crate::Constant>),
+    #[error("Alignment width {width} is not a power of two")]
+    NonPowerOfTwoWidth { width: u8 },
+    #[error("Alignment width is zero")]
+    ZeroWidth,
+}
+
+/// A bundle of the arenas shared by most of a module's global data.
+///
+/// Passes that need layout information (the [`Layouter`] here, but also
+/// backend code computing sizes on the fly) end up wanting `types` and
+/// `constants` together as a pair; bundling them here means callers pass
+/// one value instead of two, and gives us a single place to grow into
+/// (e.g. a future global-expressions arena) without touching every
+/// function signature that takes one.
+#[derive(Clone, Copy, Debug)]
+pub struct GlobalCtx<'a> {
+    pub types: &'a Arena<crate::Type>,
+    pub constants: &'a Arena<crate::Constant>,
+}
+
+impl<'a> GlobalCtx<'a> {
+    /// Compute the size in bytes that `handle` occupies under the default
+    /// (non-address-space-specific) layout rules.
+    ///
+    /// This builds a throwaway `Layouter` internally, so prefer keeping a
+    /// `Layouter` around and calling `resolve`/`resolve_for` when you need
+    /// the size of more than a handful of types.
+    pub fn type_size(&self, handle: crate::Handle<crate::Type>) -> Result<u32, LayoutError> {
+        let mut layouter = Layouter::default();
+        layouter.update(*self)?;
+        Ok(layouter.resolve(handle).size)
+    }
+}
+
+/// Which address-space layout rules apply when computing a type's layout.
+///
+/// Every address space except `Uniform` uses the permissive default
+/// layout described by https://github.com/gpuweb/gpuweb/issues/1393.
+/// `Uniform` buffers additionally require std140-style rules: structs
+/// and arrays are aligned (and array strides rounded) up to a 16-byte
+/// minimum.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum LayoutMode {
+    Storage,
+    Uniform,
+}
+
+impl LayoutMode {
+    const fn for_space(space: crate::AddressSpace) -> Self {
+        match space {
+            crate::AddressSpace::Uniform => Self::Uniform,
+            _ => Self::Storage,
+        }
+    }
+}
+
 /// Helper processor that derives the sizes of all types.
 /// It uses the default layout algorithm/table, described in
 /// https://github.com/gpuweb/gpuweb/issues/1393
 #[derive(Debug, Default)]
 pub struct Layouter {
     layouts: Vec<TypeLayout>,
+    uniform_layouts: Vec<TypeLayout>,
 }
 
 impl Layouter {
-    pub fn new(types: &Arena<crate::Type>, constants: &Arena<crate::Constant>) -> Self {
+    pub fn new(gctx: GlobalCtx) -> Self {
         let mut this = Layouter::default();
-        this.initialize(types, constants);
+        this.initialize(gctx);
         this
     }
 
-    pub fn round_up(alignment: NonZeroU32, offset: u32) -> u32 {
-        match offset & alignment.get() {
-            0 => offset,
-            other => offset + alignment.get() - other,
+    fn alignment_for_width(width: u8) -> Result<Alignment, LayoutError> {
+        match Alignment::new(width as u32) {
+            Some(alignment) => Ok(alignment),
+            None if width == 0 => Err(LayoutError::ZeroWidth),
+            None => Err(LayoutError::NonPowerOfTwoWidth { width }),
         }
     }
 
-    pub fn member_placement(
-        &self,
+    /// Compute the per-element byte stride of an array in `mode`.
+    ///
+    /// Unlike a type's `alignment`, a stride only has to be a multiple of
+    /// the base type's alignment — it has no reason to be a power of two
+    /// itself (e.g. a 3-scalar struct has size 12 and stride 12) — so this
+    /// returns a plain byte count rather than going through `Alignment`,
+    /// and can't fail on any already-computed `base_layout`.
+    fn array_stride(mode: LayoutMode, stride: Option<Alignment>, base_layout: &TypeLayout) -> u32 {
+        let stride = match stride {
+            Some(value) => value.get(),
+            None => base_layout.alignment.round_up(base_layout.size),
+        };
+        match mode {
+            LayoutMode::Storage => stride,
+            LayoutMode::Uniform => Alignment::MIN_UNIFORM.round_up(stride),
+        }
+    }
+
+    fn member_placement_in(
+        layouts: &[TypeLayout],
         offset: u32,
         member: &crate::StructMember,
-    ) -> (ops::Range<u32>, NonZeroU32) {
-        let layout = self.layouts[member.ty.index()];
+    ) -> (ops::Range<u32>, Alignment) {
+        let layout = layouts[member.ty.index()];
         let alignment = member.align.unwrap_or(layout.alignment);
-        let start = Self::round_up(alignment, offset);
+        let start = alignment.round_up(offset);
         let end = start
             + match member.size {
                 Some(size) => size.get(),
@@ -48,101 +194,454 @@ impl Layouter {
         (start..end, alignment)
     }
 
-    pub fn initialize(&mut self, types: &Arena<crate::Type>, constants: &Arena<crate::Constant>) {
-        use crate::TypeInner as Ti;
+    pub fn member_placement(
+        &self,
+        offset: u32,
+        member: &crate::StructMember,
+    ) -> (ops::Range<u32>, Alignment) {
+        Self::member_placement_in(&self.layouts, offset, member)
+    }
 
+    /// Discard all computed layouts, so the next call to `update` recomputes
+    /// every type from scratch. Call this when the type arena has been
+    /// rebuilt rather than just extended; `update` alone only ever adds to
+    /// what's already there.
+    pub fn reset(&mut self) {
         self.layouts.clear();
-        self.layouts.reserve(types.len());
+        self.uniform_layouts.clear();
+    }
 
-        for (_, ty) in types.iter() {
-            self.layouts.push(match ty.inner {
-                Ti::Scalar { kind: _, width } => TypeLayout {
-                    size: width as u32,
-                    alignment: Alignment::new(width as u32).unwrap(),
+    /// Like `update`, but panics on invalid IR instead of returning a
+    /// `LayoutError`, and always recomputes from scratch. Kept around for
+    /// callers that already guarantee their module is valid; prefer
+    /// `update` for modules coming from a front-end that hasn't validated
+    /// its input yet, or that only appends to its type arena.
+    pub fn initialize(&mut self, gctx: GlobalCtx) {
+        self.reset();
+        self.update(gctx).unwrap();
+    }
+
+    fn compute_one(
+        mode: LayoutMode,
+        ty: &crate::Type,
+        constants: &Arena<crate::Constant>,
+        layouts: &[TypeLayout],
+    ) -> Result<TypeLayout, LayoutError> {
+        use crate::TypeInner as Ti;
+
+        Ok(match ty.inner {
+            Ti::Scalar { kind: _, width } => TypeLayout {
+                size: width as u32,
+                alignment: Self::alignment_for_width(width)?,
+            },
+            Ti::Vector {
+                size,
+                kind: _,
+                width,
+            } => TypeLayout {
+                size: (size as u8 * width) as u32,
+                alignment: {
+                    let count = if size >= crate::VectorSize::Tri { 4 } else { 2 };
+                    Self::alignment_for_width(count * width)?
                 },
-                Ti::Vector {
-                    size,
-                    kind: _,
-                    width,
-                } => TypeLayout {
-                    size: (size as u8 * width) as u32,
-                    alignment: {
-                        let count = if size >= crate::VectorSize::Tri { 4 } else { 2 };
-                        Alignment::new((count * width) as u32).unwrap()
-                    },
+            },
+            Ti::Matrix {
+                columns,
+                rows,
+                width,
+            } => TypeLayout {
+                size: (columns as u8 * rows as u8 * width) as u32,
+                alignment: {
+                    let count = if rows >= crate::VectorSize::Tri { 4 } else { 2 };
+                    Self::alignment_for_width(count * width)?
                 },
-                Ti::Matrix {
-                    columns,
-                    rows,
-                    width,
-                } => TypeLayout {
-                    size: (columns as u8 * rows as u8 * width) as u32,
-                    alignment: {
-                        let count = if rows >= crate::VectorSize::Tri { 4 } else { 2 };
-                        Alignment::new((count * width) as u32).unwrap()
+            },
+            Ti::Pointer { .. } | Ti::ValuePointer { .. } => TypeLayout {
+                size: 4,
+                alignment: Alignment::ONE,
+            },
+            Ti::Array { base, size, stride } => {
+                let count = match size {
+                    crate::ArraySize::Constant(handle) => match constants[handle].inner {
+                        crate::ConstantInner::Scalar {
+                            width: _,
+                            value: crate::ScalarValue::Uint(value),
+                        } => value as u32,
+                        // Accept a signed integer size to avoid
+                        // requiring an explicit uint
+                        // literal. Type inference should make
+                        // this unnecessary.
+                        crate::ConstantInner::Scalar {
+                            width: _,
+                            value: crate::ScalarValue::Sint(value),
+                        } => value as u32,
+                        _ => return Err(LayoutError::InvalidArraySize(handle)),
+                    },
+                    crate::ArraySize::Dynamic => 0,
+                };
+                let base_layout = &layouts[base.index()];
+                let base_alignment = base_layout.alignment;
+                let byte_stride = Self::array_stride(mode, stride, base_layout);
+                TypeLayout {
+                    size: count * byte_stride,
+                    alignment: if mode == LayoutMode::Uniform {
+                        base_alignment.max(Alignment::MIN_UNIFORM)
+                    } else {
+                        base_alignment
                     },
-                },
-                Ti::Pointer { .. } | Ti::ValuePointer { .. } => TypeLayout {
-                    size: 4,
-                    alignment: Alignment::new(1).unwrap(),
-                },
-                Ti::Array { base, size, stride } => {
-                    let count = match size {
-                        crate::ArraySize::Constant(handle) => match constants[handle].inner {
-                            crate::ConstantInner::Scalar {
-                                width: _,
-                                value: crate::ScalarValue::Uint(value),
-                            } => value as u32,
-                            // Accept a signed integer size to avoid
-                            // requiring an explicit uint
-                            // literal. Type inference should make
-                            // this unnecessary.
-                            crate::ConstantInner::Scalar {
-                                width: _,
-                                value: crate::ScalarValue::Sint(value),
-                            } => value as u32,
-                            ref other => unreachable!("Unexpected array size {:?}", other),
-                        },
-                        crate::ArraySize::Dynamic => 0,
-                    };
-                    let stride = match stride {
-                        Some(value) => value,
-                        None => {
-                            let layout = &self.layouts[base.index()];
-                            let stride = Self::round_up(layout.alignment, layout.size);
-                            Alignment::new(stride).unwrap()
-                        }
-                    };
-                    TypeLayout {
-                        size: count * stride.get(),
-                        alignment: stride,
-                    }
                 }
-                Ti::Struct {
-                    block: _,
-                    ref members,
-                } => {
-                    let mut total = 0;
-                    let mut biggest_alignment = Alignment::new(1).unwrap();
-                    for member in members {
-                        let (placement, alignment) = self.member_placement(total, member);
-                        biggest_alignment = biggest_alignment.max(alignment);
-                        total = placement.end;
-                    }
-                    TypeLayout {
-                        size: Self::round_up(biggest_alignment, total),
-                        alignment: biggest_alignment,
-                    }
+            }
+            Ti::Struct {
+                block: _,
+                ref members,
+            } => {
+                let mut total = 0;
+                let mut biggest_alignment = Alignment::ONE;
+                for member in members {
+                    let (placement, alignment) = Self::member_placement_in(layouts, total, member);
+                    biggest_alignment = biggest_alignment.max(alignment);
+                    total = placement.end;
                 }
-                Ti::Image { .. } | Ti::Sampler { .. } => TypeLayout {
-                    size: 0,
-                    alignment: Alignment::new(1).unwrap(),
-                },
-            });
+                if mode == LayoutMode::Uniform {
+                    biggest_alignment = biggest_alignment.max(Alignment::MIN_UNIFORM);
+                }
+                TypeLayout {
+                    size: biggest_alignment.round_up(total),
+                    alignment: biggest_alignment,
+                }
+            }
+            Ti::Image { .. } | Ti::Sampler { .. } => TypeLayout {
+                size: 0,
+                alignment: Alignment::ONE,
+            },
+        })
+    }
+
+    /// Compute layouts for any types appended to `types` since the last
+    /// call to `update` or `reset`.
+    ///
+    /// A type can only reference types defined earlier in the same arena,
+    /// so the layouts already computed for the existing prefix remain
+    /// valid no matter what gets appended after them; only the new tail
+    /// needs to be walked. This makes repeated calls to `update` on a
+    /// module that's being built up incrementally much cheaper than
+    /// recomputing every type each time.
+    pub fn update(&mut self, gctx: GlobalCtx) -> Result<(), LayoutError> {
+        let start = self.layouts.len();
+        let additional = gctx.types.len().saturating_sub(start);
+        self.layouts.reserve(additional);
+        self.uniform_layouts.reserve(additional);
+
+        for (_, ty) in gctx.types.iter().skip(start) {
+            let layout = Self::compute_one(LayoutMode::Storage, ty, gctx.constants, &self.layouts)?;
+            self.layouts.push(layout);
+            let uniform_layout = Self::compute_one(
+                LayoutMode::Uniform,
+                ty,
+                gctx.constants,
+                &self.uniform_layouts,
+            )?;
+            self.uniform_layouts.push(uniform_layout);
         }
+
+        Ok(())
     }
 
     pub fn resolve(&self, handle: crate::Handle<crate::Type>) -> TypeLayout {
         self.layouts[handle.index()]
     }
+
+    /// Resolve a type's layout as it would apply to a variable in `space`.
+    ///
+    /// Every address space uses the natural layout except `Uniform`,
+    /// which requires std140-style struct and array alignment.
+    pub fn resolve_for(
+        &self,
+        handle: crate::Handle<crate::Type>,
+        space: crate::AddressSpace,
+    ) -> TypeLayout {
+        match LayoutMode::for_space(space) {
+            LayoutMode::Storage => self.layouts[handle.index()],
+            LayoutMode::Uniform => self.uniform_layouts[handle.index()],
+        }
+    }
+
+    /// Walk the members of the struct at `handle` the same way `update`
+    /// does for `space`, yielding each member's index, byte span, and
+    /// alignment.
+    ///
+    /// Useful for reflection/debugging front-ends (an IDE hover showing
+    /// "field `x`: offset 16, size 12, align 16") that want per-member
+    /// placement without reimplementing the struct layout algorithm
+    /// themselves. The spans differ between address spaces — a nested
+    /// member's alignment can be bumped up to 16 under `Uniform`, shifting
+    /// every later field — so `space` must match how the struct is
+    /// actually bound, the same as `resolve_for`. Panics if `handle`
+    /// doesn't refer to a `TypeInner::Struct`.
+    pub fn struct_member_spans<'a>(
+        &'a self,
+        gctx: GlobalCtx<'a>,
+        handle: crate::Handle<crate::Type>,
+        space: crate::AddressSpace,
+    ) -> impl Iterator<Item = (usize, ops::Range<u32>, Alignment)> + 'a {
+        let members = match gctx.types[handle].inner {
+            crate::TypeInner::Struct { ref members, .. } => members,
+            _ => panic!("struct_member_spans called on a non-struct type"),
+        };
+        let layouts = match LayoutMode::for_space(space) {
+            LayoutMode::Storage => &self.layouts,
+            LayoutMode::Uniform => &self.uniform_layouts,
+        };
+        let mut offset = 0;
+        members.iter().enumerate().map(move |(index, member)| {
+            let (span, alignment) = Self::member_placement_in(layouts, offset, member);
+            offset = span.end;
+            (index, span, alignment)
+        })
+    }
+}
+
+impl ops::Index<crate::Handle<crate::Type>> for Layouter {
+    type Output = TypeLayout;
+    fn index(&self, handle: crate::Handle<crate::Type>) -> &TypeLayout {
+        &self.layouts[handle.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ArraySize, Constant, ConstantInner, ScalarKind, ScalarValue, Span, StructMember, Type,
+        TypeInner,
+    };
+
+    #[test]
+    fn round_up_and_is_aligned() {
+        assert_eq!(Alignment::FOUR.round_up(0), 0);
+        assert_eq!(Alignment::FOUR.round_up(1), 4);
+        assert_eq!(Alignment::FOUR.round_up(4), 4);
+        assert_eq!(Alignment::SIXTEEN.round_up(12), 16);
+        assert_eq!(Alignment::SIXTEEN.round_up(17), 32);
+
+        assert!(Alignment::FOUR.is_aligned(0));
+        assert!(Alignment::FOUR.is_aligned(8));
+        assert!(!Alignment::FOUR.is_aligned(6));
+    }
+
+    #[test]
+    fn array_stride_is_not_forced_through_alignment() {
+        // A 3-scalar struct: size 12, align 4. Its natural stride (12) is
+        // not a power of two, so it can't be represented as an `Alignment`.
+        let base = TypeLayout {
+            size: 12,
+            alignment: Alignment::FOUR,
+        };
+        assert_eq!(Layouter::array_stride(LayoutMode::Storage, None, &base), 12);
+        // In the Uniform address space the stride is rounded up to 16.
+        assert_eq!(Layouter::array_stride(LayoutMode::Uniform, None, &base), 16);
+    }
+
+    fn append_type(types: &mut Arena<Type>, inner: TypeInner) -> crate::Handle<Type> {
+        types.append(Type { name: None, inner }, Span::UNDEFINED)
+    }
+
+    // `struct Foo { a: f32, b: f32, c: f32 }` used as `array<Foo, 4>`.
+    fn build_module() -> (Arena<Type>, Arena<Constant>, crate::Handle<Type>, crate::Handle<Type>) {
+        let mut types = Arena::new();
+        let mut constants = Arena::new();
+
+        let f32_ty = append_type(
+            &mut types,
+            TypeInner::Scalar {
+                kind: ScalarKind::Float,
+                width: 4,
+            },
+        );
+        let foo_ty = append_type(
+            &mut types,
+            TypeInner::Struct {
+                block: false,
+                members: vec![
+                    StructMember {
+                        ty: f32_ty,
+                        align: None,
+                        size: None,
+                    },
+                    StructMember {
+                        ty: f32_ty,
+                        align: None,
+                        size: None,
+                    },
+                    StructMember {
+                        ty: f32_ty,
+                        align: None,
+                        size: None,
+                    },
+                ],
+            },
+        );
+        let count = constants.append(
+            Constant {
+                name: None,
+                specialization: None,
+                inner: ConstantInner::Scalar {
+                    width: 4,
+                    value: ScalarValue::Uint(4),
+                },
+            },
+            Span::UNDEFINED,
+        );
+        let array_ty = append_type(
+            &mut types,
+            TypeInner::Array {
+                base: foo_ty,
+                size: ArraySize::Constant(count),
+                stride: None,
+            },
+        );
+
+        (types, constants, foo_ty, array_ty)
+    }
+
+    #[test]
+    fn struct_and_array_layout_storage_vs_uniform() {
+        let (types, constants, foo_ty, array_ty) = build_module();
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        let mut layouter = Layouter::default();
+        layouter.update(gctx).unwrap();
+
+        // Storage: size 12, align 4, stride 12 -> array size 48.
+        let foo_storage = layouter.resolve(foo_ty);
+        assert_eq!(foo_storage.size, 12);
+        assert_eq!(foo_storage.alignment, Alignment::FOUR);
+        let array_storage = layouter.resolve(array_ty);
+        assert_eq!(array_storage.size, 48);
+        assert_eq!(array_storage.alignment, Alignment::FOUR);
+
+        // Uniform: struct padded to align 16, array stride padded to 16
+        // -> array size 64.
+        let foo_uniform = layouter.resolve_for(foo_ty, crate::AddressSpace::Uniform);
+        assert_eq!(foo_uniform.alignment, Alignment::SIXTEEN);
+        let array_uniform = layouter.resolve_for(array_ty, crate::AddressSpace::Uniform);
+        assert_eq!(array_uniform.size, 64);
+        assert_eq!(array_uniform.alignment, Alignment::SIXTEEN);
+    }
+
+    #[test]
+    fn update_is_incremental() {
+        let (mut types, constants, foo_ty, _array_ty) = build_module();
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        let mut layouter = Layouter::default();
+        layouter.update(gctx).unwrap();
+        assert_eq!(layouter.resolve(foo_ty).size, 12);
+
+        // Appending a new type and calling `update` again must not
+        // recompute (or invalidate) the already-resolved prefix.
+        let extra_ty = append_type(
+            &mut types,
+            TypeInner::Scalar {
+                kind: ScalarKind::Sint,
+                width: 4,
+            },
+        );
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        layouter.update(gctx).unwrap();
+        assert_eq!(layouter.resolve(foo_ty).size, 12);
+        assert_eq!(layouter.resolve(extra_ty).size, 4);
+    }
+
+    #[test]
+    fn update_reports_non_power_of_two_width_instead_of_panicking() {
+        let mut types = Arena::new();
+        let constants = Arena::new();
+        append_type(
+            &mut types,
+            TypeInner::Scalar {
+                kind: ScalarKind::Float,
+                width: 3,
+            },
+        );
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        let mut layouter = Layouter::default();
+        assert_eq!(
+            layouter.update(gctx),
+            Err(LayoutError::NonPowerOfTwoWidth { width: 3 })
+        );
+    }
+
+    #[test]
+    fn update_reports_zero_width_instead_of_panicking() {
+        let mut types = Arena::new();
+        let constants = Arena::new();
+        append_type(
+            &mut types,
+            TypeInner::Scalar {
+                kind: ScalarKind::Float,
+                width: 0,
+            },
+        );
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        let mut layouter = Layouter::default();
+        assert_eq!(layouter.update(gctx), Err(LayoutError::ZeroWidth));
+    }
+
+    #[test]
+    fn update_reports_invalid_array_size_instead_of_panicking() {
+        let mut types = Arena::new();
+        let mut constants = Arena::new();
+        // An array size constant that isn't an integer scalar (e.g. a
+        // front-end that hasn't resolved a specialization/override
+        // constant yet) must not hit the old `unreachable!()`.
+        let size = constants.append(
+            Constant {
+                name: None,
+                specialization: None,
+                inner: ConstantInner::Scalar {
+                    width: 4,
+                    value: ScalarValue::Float(1.0),
+                },
+            },
+            Span::UNDEFINED,
+        );
+        let f32_ty = append_type(
+            &mut types,
+            TypeInner::Scalar {
+                kind: ScalarKind::Float,
+                width: 4,
+            },
+        );
+        append_type(
+            &mut types,
+            TypeInner::Array {
+                base: f32_ty,
+                size: ArraySize::Constant(size),
+                stride: None,
+            },
+        );
+        let gctx = GlobalCtx {
+            types: &types,
+            constants: &constants,
+        };
+        let mut layouter = Layouter::default();
+        assert_eq!(
+            layouter.update(gctx),
+            Err(LayoutError::InvalidArraySize(size))
+        );
+    }
 }